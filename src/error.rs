@@ -0,0 +1,51 @@
+use std::fmt;
+
+/// Errors that can occur while reading registers from a Modbus transport
+#[derive(Debug)]
+pub enum Error {
+    /// The underlying `tokio_modbus` transport returned an error
+    Modbus(tokio_modbus::Error),
+    /// An I/O error occurred while talking to the transport
+    Io(std::io::Error),
+    /// An RTU response failed its CRC-16/Modbus check
+    Crc,
+    /// An RTU device returned a Modbus exception, or an unexpected function code
+    RtuException(u8),
+    /// An RTU request or response did not complete within the allotted time
+    Timeout,
+    /// A `RegisterMap` did not define a register that `get_measurements` needs
+    MissingRegister(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Modbus(e) => write!(f, "modbus error: {}", e),
+            Error::Io(e) => write!(f, "i/o error: {}", e),
+            Error::Crc => write!(f, "CRC-16 mismatch in RTU response"),
+            Error::RtuException(code) => write!(f, "RTU exception/unexpected code: {:#04x}", code),
+            Error::Timeout => write!(f, "RTU request timed out"),
+            Error::MissingRegister(name) => write!(f, "register map has no entry named \"{}\"", name),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<tokio_modbus::Error> for Error {
+    fn from(e: tokio_modbus::Error) -> Self {
+        Error::Modbus(e)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<tokio_serial::Error> for Error {
+    fn from(e: tokio_serial::Error) -> Self {
+        Error::Io(e.into())
+    }
+}
@@ -1,84 +1,97 @@
 use log::*;
+use serde::{Deserialize, Serialize};
+use uom::si::electric_current::ampere;
+use uom::si::electric_potential::volt;
+use uom::si::f32::{ElectricCurrent, ElectricPotential, Frequency, Power, ThermodynamicTemperature};
+use uom::si::frequency::hertz;
+use uom::si::power::watt;
+use uom::si::thermodynamic_temperature::degree_celsius;
 
+mod error;
 mod modbus_datatypes;
-use modbus_datatypes::ModbusConversions;
+#[cfg(test)]
+mod mock;
+mod poll;
+mod queue;
+mod register_map;
+mod rtu;
+mod transport;
+
+use error::Error;
+use poll::{PollConfig, TransportConfig};
+use register_map::{default_register_map, read_register_map, RegisterMap};
+use rtu::RtuSettings;
+use transport::RegisterSource;
 
 /// Structure to store counter data in types compatible with psql
-struct Counter {
-    exp: i32,
-    mantissa: i32,
-    val: f32,
-    x10: f32,
-    float: f32,
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Counter {
+    pub(crate) exp: i32,
+    pub(crate) mantissa: i32,
+    pub(crate) val: f32,
+    pub(crate) x10: f32,
+    pub(crate) float: f32,
 }
 
-/// Structure to store measurements in types compatible with psql
-struct Measurement {
-    device_id: i32,
-    device_timestamp: i32,
-    frequency: f32,
-    u1: f32,
-    i1: f32,
-    pt: f32,
-    qt: f32,
-    st: f32,
-    pft: i32,
-    temp: f32,
-    u1_thd: f32,
-    i1_thd: f32,
-    c1: Counter,
-    c4: Counter,
-    x3: Counter,
+/// Structure to store measurements with each physical quantity carrying its
+/// own unit, converted to plain numbers only when written to psql (hertz,
+/// volts, amperes and watts; temperature in degrees Celsius)
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Measurement {
+    pub(crate) device_id: i32,
+    pub(crate) device_timestamp: i32,
+    pub(crate) frequency: Frequency,
+    pub(crate) u1: ElectricPotential,
+    pub(crate) i1: ElectricCurrent,
+    pub(crate) pt: Power,
+    pub(crate) qt: Power,
+    pub(crate) st: Power,
+    pub(crate) pft: i32,
+    pub(crate) temp: ThermodynamicTemperature,
+    pub(crate) u1_thd: f32,
+    pub(crate) i1_thd: f32,
+    pub(crate) c1: Counter,
+    pub(crate) c4: Counter,
+    pub(crate) x3: Counter,
 }
 
-/// Connect to modbus server and get all measurements
-async fn get_measurements(
-    modbus_conn_string: String,
+/// Read all measurements from a `RegisterSource`, using `register_map` to
+/// decide which registers to read and how to decode them
+async fn get_measurements<R: RegisterSource + ?Sized>(
+    source: &mut R,
     device_id: u8,
-) -> Result<Measurement, Box<dyn std::error::Error>> {
-    use tokio_modbus::prelude::*;
-
-    let socket_addr = modbus_conn_string.parse().unwrap();
-    let mut ctx = tcp::connect(socket_addr).await?;
-    ctx.set_slave(Slave(device_id));
-
-    let m_runtime = read_finder_register!(ctx, "Run time", 103, 2, get_t3);
-    let m_freq = read_finder_register!(ctx, "Frequency", 105, 2, get_t5);
-    let m_u1 = read_finder_register!(ctx, "U1", 107, 2, get_t5);
-    let m_i1 = read_finder_register!(ctx, "I1", 126, 2, get_t5);
-    let m_pt = read_finder_register!(ctx, "Active power total", 140, 2, get_t6);
-    let m_qt = read_finder_register!(ctx, "Reactive power total", 148, 2, get_t6);
-    let m_st = read_finder_register!(ctx, "Apparent power total", 156, 2, get_t5);
-    let m_pft = read_finder_register!(ctx, "Power factor total", 164, 2, get_t7);
-    let m_temp = read_finder_register!(ctx, "Internal temperature", 181, 1, get_t17);
-    let m_u1_thd = read_finder_register!(ctx, "U1 THD%", 182, 1, get_t17);
-    let m_i1_thd = read_finder_register!(ctx, "I1 THD%", 188, 1, get_t17);
-
-    // // C1 (MID certified) - Import Active Energy
-    let counter_c1 = read_finder_counter!(ctx, "C1", 401, 406, 462, 2638);
-
-    // C4 (MID Certified) - Export reactive energy
-    let counter_c4 = read_finder_counter!(ctx, "C4", 404, 412, 468, 2644);
-
-    // X3 (not certified) - Total Absolute Apparent Energy
-    let counter_x3 = read_finder_counter!(ctx, "X3", 448, 418, 474, 2764);
+    register_map: &RegisterMap,
+) -> Result<Measurement, Error> {
+    let (values, mut counters) = read_register_map(source, register_map).await?;
+
+    let value = |name: &'static str| {
+        values
+            .get(name)
+            .copied()
+            .ok_or(Error::MissingRegister(name.to_string()))
+    };
+    let counter = |name: &'static str| {
+        counters
+            .remove(name)
+            .ok_or(Error::MissingRegister(name.to_string()))
+    };
 
     let measurement = Measurement {
         device_id: device_id as i32,
-        device_timestamp: m_runtime,
-        frequency: m_freq,
-        u1: m_u1,
-        i1: m_i1,
-        u1_thd: m_u1_thd,
-        i1_thd: m_i1_thd,
-        pt: m_pt,
-        qt: m_qt,
-        st: m_st,
-        pft: m_pft,
-        temp: m_temp,
-        c1: counter_c1,
-        c4: counter_c4,
-        x3: counter_x3,
+        device_timestamp: value("runtime")?.as_i32(),
+        frequency: Frequency::new::<hertz>(value("frequency")?.as_f32()),
+        u1: ElectricPotential::new::<volt>(value("u1")?.as_f32()),
+        i1: ElectricCurrent::new::<ampere>(value("i1")?.as_f32()),
+        pt: Power::new::<watt>(value("pt")?.as_f32()),
+        qt: Power::new::<watt>(value("qt")?.as_f32()),
+        st: Power::new::<watt>(value("st")?.as_f32()),
+        pft: value("pft")?.as_i32(),
+        temp: ThermodynamicTemperature::new::<degree_celsius>(value("temp")?.as_f32()),
+        u1_thd: value("u1_thd")?.as_f32(),
+        i1_thd: value("i1_thd")?.as_f32(),
+        c1: counter("c1")?,
+        c4: counter("c4")?,
+        x3: counter("x3")?,
     };
 
     Ok(measurement)
@@ -90,6 +103,11 @@ async fn write_to_psql(
     measurement: Measurement,
 ) -> Result<(), Box<dyn std::error::Error>> {
     use tokio_postgres::NoTls;
+    use uom::si::electric_current::ampere;
+    use uom::si::electric_potential::volt;
+    use uom::si::frequency::hertz;
+    use uom::si::power::watt;
+    use uom::si::thermodynamic_temperature::degree_celsius;
 
     let (client, connection) = tokio_postgres::connect(&psql_conn_string, NoTls).await?;
 
@@ -98,6 +116,15 @@ async fn write_to_psql(
             error!("connection error: {}", e);
         }
     });
+
+    let frequency = measurement.frequency.get::<hertz>();
+    let u1 = measurement.u1.get::<volt>();
+    let i1 = measurement.i1.get::<ampere>();
+    let pt = measurement.pt.get::<watt>();
+    let qt = measurement.qt.get::<watt>();
+    let st = measurement.st.get::<watt>();
+    let temp = measurement.temp.get::<degree_celsius>();
+
     client.execute("INSERT INTO energy \
         (device_id, device_timestamp, frequency, U1, I1, \
         Pt, Qt, St, Pft, int_temp, \
@@ -106,13 +133,13 @@ async fn write_to_psql(
         c4_exp, c4_mantissa, c4_val, c4_x10, c4_float,\
         x3_exp, x3_mantissa, x3_val, x3_x10, x3_float) \
     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27)",
-                   &[&measurement.device_id, &measurement.device_timestamp, &measurement.frequency, &measurement.u1, &measurement.i1,
-                       &measurement.pt, &measurement.qt, &measurement.st, &measurement.pft, &measurement.temp,
+                   &[&measurement.device_id, &measurement.device_timestamp, &frequency, &u1, &i1,
+                       &pt, &qt, &st, &measurement.pft, &temp,
                        &measurement.u1_thd, &measurement.i1_thd,
                        &measurement.c1.exp, &measurement.c1.mantissa, &measurement.c1.val, &measurement.c1.x10, &measurement.c1.float,
                        &measurement.c4.exp, &measurement.c4.mantissa, &measurement.c4.val, &measurement.c4.x10, &measurement.c4.float,
                        &measurement.x3.exp, &measurement.x3.mantissa, &measurement.x3.val, &measurement.x3.x10, &measurement.x3.float])
-        .await.expect("Cannot write into database");
+        .await?;
 
     Ok(())
 }
@@ -121,6 +148,7 @@ async fn write_to_psql(
 pub async fn main() -> Result<(), Box<dyn std::error::Error>> {
     use config::Config;
     use std::str::FromStr;
+    use std::time::Duration;
 
     let settings = Config::builder()
         .add_source(config::File::with_name("Settings"))
@@ -135,14 +163,160 @@ pub async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .init()
         .unwrap();
 
-    let modbus_addr: String = settings.get_string("modbus_server").unwrap();
     let modbus_device_id: u8 = settings.get_int("modbus_device_id").unwrap() as u8;
     let psql_addr: String = settings.get_string("psql").unwrap();
 
-    let measurement = get_measurements(modbus_addr, modbus_device_id)
-        .await
-        .unwrap();
-    write_to_psql(psql_addr, measurement).await.unwrap();
+    let transport_kind = settings
+        .get_string("transport")
+        .unwrap_or_else(|_| "tcp".to_string());
 
-    Ok(())
+    let transport = match transport_kind.as_str() {
+        "rtu" => TransportConfig::Rtu(RtuSettings {
+            device: settings.get_string("rtu_device").unwrap(),
+            baud_rate: settings.get_int("rtu_baud_rate").unwrap() as u32,
+            data_bits: parse_data_bits(&settings.get_string("rtu_data_bits").unwrap()),
+            parity: parse_parity(&settings.get_string("rtu_parity").unwrap()),
+            stop_bits: parse_stop_bits(&settings.get_string("rtu_stop_bits").unwrap()),
+            slave_id: modbus_device_id,
+        }),
+        _ => {
+            let modbus_addr: String = settings.get_string("modbus_server").unwrap();
+            TransportConfig::Tcp {
+                socket_addr: modbus_addr.parse().unwrap(),
+            }
+        }
+    };
+
+    let register_map = settings
+        .get::<RegisterMap>("register_map")
+        .unwrap_or_else(|_| default_register_map());
+
+    let poll_interval = Duration::from_secs(settings.get_int("poll_interval_seconds").unwrap_or(60) as u64);
+    // Clamped to at least 1 so a `max_retries = 0` config tries once instead
+    // of hitting the `unreachable!` in poll.rs's retry loops
+    let max_retries = (settings.get_int("max_retries").unwrap_or(5) as u32).max(1);
+    let retry_backoff = Duration::from_secs(settings.get_int("retry_backoff_seconds").unwrap_or(1) as u64);
+    let queue_path = settings
+        .get_string("queue_path")
+        .unwrap_or_else(|_| "measurement_queue.jsonl".to_string())
+        .into();
+
+    let config = PollConfig {
+        transport,
+        device_id: modbus_device_id,
+        register_map,
+        psql_addr,
+        poll_interval,
+        max_retries,
+        retry_backoff,
+        queue_path,
+    };
+
+    poll::run(config).await
+}
+
+/// Parse the `rtu_data_bits` setting (5-8)
+fn parse_data_bits(s: &str) -> tokio_serial::DataBits {
+    use tokio_serial::DataBits;
+    match s {
+        "5" => DataBits::Five,
+        "6" => DataBits::Six,
+        "7" => DataBits::Seven,
+        "8" => DataBits::Eight,
+        other => panic!("invalid rtu_data_bits: {}", other),
+    }
+}
+
+/// Parse the `rtu_parity` setting ("none", "odd", "even")
+fn parse_parity(s: &str) -> tokio_serial::Parity {
+    use tokio_serial::Parity;
+    match s {
+        "none" => Parity::None,
+        "odd" => Parity::Odd,
+        "even" => Parity::Even,
+        other => panic!("invalid rtu_parity: {}", other),
+    }
+}
+
+/// Parse the `rtu_stop_bits` setting ("1", "2")
+fn parse_stop_bits(s: &str) -> tokio_serial::StopBits {
+    use tokio_serial::StopBits;
+    match s {
+        "1" => StopBits::One,
+        "2" => StopBits::Two,
+        other => panic!("invalid rtu_stop_bits: {}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockDevice;
+    use uom::si::electric_current::ampere;
+    use uom::si::electric_potential::volt;
+    use uom::si::frequency::hertz;
+    use uom::si::power::watt;
+    use uom::si::thermodynamic_temperature::degree_celsius;
+
+    /// Seed one energy counter's four sub-registers so that exp=0, mantissa=100,
+    /// x10=10.0 and float=123.45
+    fn seed_counter(
+        device: &mut MockDevice,
+        addr_exp: u16,
+        addr_mantissa: u16,
+        addr_x10: u16,
+        addr_float: u16,
+    ) {
+        device.set(addr_exp, vec![0x0000]);
+        device.set(addr_mantissa, vec![0x0000, 0x0064]);
+        device.set(addr_x10, vec![0x0000, 0x0064]);
+        device.set(addr_float, vec![0x42F6, 0xE666]);
+    }
+
+    #[tokio::test]
+    async fn test_get_measurements_against_mock_device() {
+        let mut device = MockDevice::new();
+
+        device.set(103, vec![0x0000, 0x0001]); // Run time
+        device.set(105, vec![0xFD01, 0xE240]); // Frequency
+        device.set(107, vec![0xFD01, 0xE240]); // U1
+        device.set(126, vec![0xFD01, 0xE240]); // I1
+        device.set(140, vec![0xFDFE, 0x1DC0]); // Active power total
+        device.set(148, vec![0xFDFE, 0x1DC0]); // Reactive power total
+        device.set(156, vec![0xFD01, 0xE240]); // Apparent power total
+        device.set(164, vec![0x0000, 0x2710]); // Power factor total
+        device.set(181, vec![0xCFC7]); // Internal temperature
+        device.set(182, vec![0x3039]); // U1 THD%
+        device.set(188, vec![0x3039]); // I1 THD%
+
+        seed_counter(&mut device, 401, 406, 462, 2638); // C1
+        seed_counter(&mut device, 404, 412, 468, 2644); // C4
+        seed_counter(&mut device, 448, 418, 474, 2764); // X3
+
+        let register_map = default_register_map();
+        let measurement = get_measurements(&mut device, 42, &register_map)
+            .await
+            .expect("get_measurements should succeed against a fully-seeded mock");
+
+        assert_eq!(measurement.device_id, 42);
+        assert_eq!(measurement.device_timestamp, 1);
+        assert_eq!(measurement.frequency.get::<hertz>(), 123.45601_f32);
+        assert_eq!(measurement.u1.get::<volt>(), 123.45601_f32);
+        assert_eq!(measurement.i1.get::<ampere>(), 123.45601_f32);
+        assert_eq!(measurement.pt.get::<watt>(), -123.45601_f32);
+        assert_eq!(measurement.qt.get::<watt>(), -123.45601_f32);
+        assert_eq!(measurement.st.get::<watt>(), 123.45601_f32);
+        assert_eq!(measurement.pft, 10000);
+        assert_eq!(measurement.temp.get::<degree_celsius>(), -123.45);
+        assert_eq!(measurement.u1_thd, 123.45);
+        assert_eq!(measurement.i1_thd, 123.45);
+
+        for counter in [&measurement.c1, &measurement.c4, &measurement.x3] {
+            assert_eq!(counter.exp, 0);
+            assert_eq!(counter.mantissa, 100);
+            assert_eq!(counter.x10, 10.0);
+            assert_eq!(counter.float, 123.45);
+            assert_eq!(counter.val, 100.0);
+        }
+    }
 }
@@ -0,0 +1,299 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use log::{error, warn};
+
+use crate::error::Error;
+use crate::queue::MeasurementQueue;
+use crate::register_map::RegisterMap;
+use crate::rtu::{RtuRegisterSource, RtuSettings};
+use crate::transport::{RegisterSource, TcpRegisterSource};
+use crate::{get_measurements, write_to_psql, Measurement};
+
+/// Which modbus transport a poll cycle should connect over
+pub enum TransportConfig {
+    Tcp { socket_addr: std::net::SocketAddr },
+    Rtu(RtuSettings),
+}
+
+/// Configuration for the polling daemon
+pub struct PollConfig {
+    pub transport: TransportConfig,
+    pub device_id: u8,
+    pub register_map: RegisterMap,
+    pub psql_addr: String,
+    pub poll_interval: Duration,
+    pub max_retries: u32,
+    pub retry_backoff: Duration,
+    pub queue_path: PathBuf,
+}
+
+/// Poll forever at `config.poll_interval`. Transient modbus or psql failures
+/// are retried with backoff; measurements that still can't be written are
+/// buffered to disk and flushed in order once psql is reachable again.
+pub async fn run(config: PollConfig) -> ! {
+    let queue = MeasurementQueue::new(config.queue_path.clone());
+    let mut ticker = tokio::time::interval(config.poll_interval);
+
+    loop {
+        ticker.tick().await;
+
+        if let Err(e) = flush_queue(&queue, &config).await {
+            error!("failed to flush buffered measurements: {}", e);
+        }
+
+        let mut source = match connect_with_retry(
+            &config.transport,
+            config.device_id,
+            config.max_retries,
+            config.retry_backoff,
+        )
+        .await
+        {
+            Ok(source) => source,
+            Err(e) => {
+                error!("giving up on connecting to modbus transport after retries: {}", e);
+                continue;
+            }
+        };
+
+        let measurement = match read_with_retry(
+            source.as_mut(),
+            config.device_id,
+            &config.register_map,
+            config.max_retries,
+            config.retry_backoff,
+        )
+        .await
+        {
+            Ok(measurement) => measurement,
+            Err(e) => {
+                error!("giving up on this poll after retries: {}", e);
+                continue;
+            }
+        };
+
+        match write_with_retry(
+            &config.psql_addr,
+            &measurement,
+            config.max_retries,
+            config.retry_backoff,
+        )
+        .await
+        {
+            Ok(()) => {}
+            Err(e) => {
+                warn!("could not write measurement, buffering to disk: {}", e);
+                if let Err(e) = queue.push(&measurement) {
+                    error!("could not buffer measurement to disk: {}", e);
+                }
+            }
+        }
+    }
+}
+
+/// Connect to the configured modbus transport
+async fn connect_source(
+    transport: &TransportConfig,
+    device_id: u8,
+) -> Result<Box<dyn RegisterSource>, Error> {
+    match transport {
+        TransportConfig::Tcp { socket_addr } => {
+            Ok(Box::new(TcpRegisterSource::connect(*socket_addr, device_id).await?))
+        }
+        TransportConfig::Rtu(settings) => Ok(Box::new(RtuRegisterSource::open(settings)?)),
+    }
+}
+
+/// Connect to the configured modbus transport, retrying transient failures
+/// with exponential backoff
+async fn connect_with_retry(
+    transport: &TransportConfig,
+    device_id: u8,
+    max_attempts: u32,
+    initial_delay: Duration,
+) -> Result<Box<dyn RegisterSource>, Error> {
+    let mut delay = initial_delay;
+    for attempt in 1..=max_attempts {
+        match connect_source(transport, device_id).await {
+            Ok(source) => return Ok(source),
+            Err(e) if attempt == max_attempts => return Err(e),
+            Err(e) => {
+                warn!(
+                    "modbus connect attempt {}/{} failed: {}, retrying in {:?}",
+                    attempt, max_attempts, e, delay
+                );
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+        }
+    }
+    unreachable!("max_attempts is always at least 1")
+}
+
+/// Read a measurement, retrying transient failures with exponential backoff
+async fn read_with_retry<R: RegisterSource + ?Sized>(
+    source: &mut R,
+    device_id: u8,
+    register_map: &RegisterMap,
+    max_attempts: u32,
+    initial_delay: Duration,
+) -> Result<Measurement, Error> {
+    let mut delay = initial_delay;
+    for attempt in 1..=max_attempts {
+        match get_measurements(source, device_id, register_map).await {
+            Ok(measurement) => return Ok(measurement),
+            Err(e) if attempt == max_attempts => return Err(e),
+            Err(e) => {
+                warn!(
+                    "modbus read attempt {}/{} failed: {}, retrying in {:?}",
+                    attempt, max_attempts, e, delay
+                );
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+        }
+    }
+    unreachable!("max_attempts is always at least 1")
+}
+
+/// Write a measurement to psql, retrying transient failures with
+/// exponential backoff
+async fn write_with_retry(
+    psql_addr: &str,
+    measurement: &Measurement,
+    max_attempts: u32,
+    initial_delay: Duration,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut delay = initial_delay;
+    for attempt in 1..=max_attempts {
+        match write_to_psql(psql_addr.to_string(), measurement.clone()).await {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt == max_attempts => return Err(e),
+            Err(e) => {
+                warn!(
+                    "psql write attempt {}/{} failed: {}, retrying in {:?}",
+                    attempt, max_attempts, e, delay
+                );
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+        }
+    }
+    unreachable!("max_attempts is always at least 1")
+}
+
+/// Try to write out every buffered measurement, in order, stopping (and
+/// keeping the remainder queued) at the first failure
+async fn flush_queue(
+    queue: &MeasurementQueue,
+    config: &PollConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let buffered = queue.drain()?;
+    for (i, measurement) in buffered.iter().enumerate() {
+        if let Err(e) = write_to_psql(config.psql_addr.clone(), measurement.clone()).await {
+            warn!(
+                "flush stopped at buffered measurement {}/{}: {}",
+                i + 1,
+                buffered.len(),
+                e
+            );
+            queue.rewrite(&buffered[i..])?;
+            return Ok(());
+        }
+    }
+    queue.rewrite(&[])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::register_map::default_register_map;
+    use crate::Counter;
+    use async_trait::async_trait;
+    use uom::si::electric_current::ampere;
+    use uom::si::electric_potential::volt;
+    use uom::si::f32::{ElectricCurrent, ElectricPotential, Frequency, Power, ThermodynamicTemperature};
+    use uom::si::frequency::hertz;
+    use uom::si::power::watt;
+    use uom::si::thermodynamic_temperature::degree_celsius;
+
+    /// A `RegisterSource` that always fails, to exercise give-up-after-N-attempts
+    struct AlwaysFailingSource {
+        attempts: u32,
+    }
+
+    #[async_trait]
+    impl RegisterSource for AlwaysFailingSource {
+        async fn read_input_registers(&mut self, _addr: u16, _count: u16) -> Result<Vec<u16>, Error> {
+            self.attempts += 1;
+            Err(Error::Timeout)
+        }
+    }
+
+    fn sample_measurement() -> Measurement {
+        let counter = Counter {
+            exp: 0,
+            mantissa: 100,
+            val: 100.0,
+            x10: 10.0,
+            float: 123.45,
+        };
+
+        Measurement {
+            device_id: 1,
+            device_timestamp: 1,
+            frequency: Frequency::new::<hertz>(50.0),
+            u1: ElectricPotential::new::<volt>(230.0),
+            i1: ElectricCurrent::new::<ampere>(1.0),
+            pt: Power::new::<watt>(100.0),
+            qt: Power::new::<watt>(10.0),
+            st: Power::new::<watt>(101.0),
+            pft: 9800,
+            temp: ThermodynamicTemperature::new::<degree_celsius>(25.0),
+            u1_thd: 1.2,
+            i1_thd: 1.3,
+            c1: counter.clone(),
+            c4: counter.clone(),
+            x3: counter,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_with_retry_gives_up_after_max_attempts() {
+        let mut source = AlwaysFailingSource { attempts: 0 };
+        let register_map = default_register_map();
+
+        let result = read_with_retry(&mut source, 1, &register_map, 3, Duration::from_millis(1)).await;
+
+        assert!(result.is_err());
+        assert_eq!(source.attempts, 3);
+    }
+
+    #[tokio::test]
+    async fn test_connect_with_retry_gives_up_after_max_attempts() {
+        // Nothing listens on this port, so every attempt fails immediately
+        let transport = TransportConfig::Tcp {
+            socket_addr: "127.0.0.1:1".parse().unwrap(),
+        };
+
+        let result = connect_with_retry(&transport, 1, 2, Duration::from_millis(1)).await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_write_with_retry_gives_up_after_max_attempts() {
+        // Nothing listens on this port, so every attempt fails immediately
+        let result = write_with_retry(
+            "postgresql://127.0.0.1:1/nonexistent",
+            &sample_measurement(),
+            2,
+            Duration::from_millis(1),
+        )
+        .await;
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,174 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+use log::warn;
+
+use crate::Measurement;
+
+/// Append-only on-disk queue of measurements that could not be written to
+/// psql, flushed in order once the database is reachable again
+pub struct MeasurementQueue {
+    path: PathBuf,
+}
+
+impl MeasurementQueue {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Append `measurement` to the queue
+    pub fn push(&self, measurement: &Measurement) -> io::Result<()> {
+        let line = serde_json::to_string(measurement)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", line)
+    }
+
+    /// Read every buffered measurement, in the order they were queued.
+    ///
+    /// A line that fails to parse (e.g. the process was killed mid-`writeln!`)
+    /// is logged and skipped rather than failing the whole batch, so one
+    /// corrupt line can't strand every measurement around it forever.
+    pub fn drain(&self) -> io::Result<Vec<Measurement>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut measurements = Vec::new();
+        for (i, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            match serde_json::from_str(&line) {
+                Ok(measurement) => measurements.push(measurement),
+                Err(e) => warn!("skipping unparseable queued measurement at line {}: {}", i + 1, e),
+            }
+        }
+        Ok(measurements)
+    }
+
+    /// Overwrite the queue with exactly `measurements`, in order
+    pub fn rewrite(&self, measurements: &[Measurement]) -> io::Result<()> {
+        let mut file = File::create(&self.path)?;
+        for measurement in measurements {
+            writeln!(file, "{}", serde_json::to_string(measurement)?)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Counter;
+    use std::fs;
+    use uom::si::electric_current::ampere;
+    use uom::si::electric_potential::volt;
+    use uom::si::f32::{ElectricCurrent, ElectricPotential, Frequency, Power, ThermodynamicTemperature};
+    use uom::si::frequency::hertz;
+    use uom::si::power::watt;
+    use uom::si::thermodynamic_temperature::degree_celsius;
+
+    /// A `Measurement` exercising every uom-typed field, so push/drain also
+    /// exercises serializing those types through serde_json
+    fn sample_measurement(device_id: i32) -> Measurement {
+        let counter = Counter {
+            exp: 0,
+            mantissa: 100,
+            val: 100.0,
+            x10: 10.0,
+            float: 123.45,
+        };
+
+        Measurement {
+            device_id,
+            device_timestamp: 1,
+            frequency: Frequency::new::<hertz>(50.0),
+            u1: ElectricPotential::new::<volt>(230.0),
+            i1: ElectricCurrent::new::<ampere>(1.0),
+            pt: Power::new::<watt>(100.0),
+            qt: Power::new::<watt>(10.0),
+            st: Power::new::<watt>(101.0),
+            pft: 9800,
+            temp: ThermodynamicTemperature::new::<degree_celsius>(25.0),
+            u1_thd: 1.2,
+            i1_thd: 1.3,
+            c1: counter.clone(),
+            c4: counter.clone(),
+            x3: counter,
+        }
+    }
+
+    /// A queue path unique to this test, under the OS temp dir
+    fn temp_queue_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("rs485_queue_test_{}_{}.jsonl", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_push_and_drain_round_trip() {
+        let path = temp_queue_path("round_trip");
+        let _ = fs::remove_file(&path);
+        let queue = MeasurementQueue::new(path.clone());
+
+        queue.push(&sample_measurement(1)).unwrap();
+        queue.push(&sample_measurement(2)).unwrap();
+
+        let drained = queue.drain().unwrap();
+        assert_eq!(drained.len(), 2);
+        assert_eq!(drained[0].device_id, 1);
+        assert_eq!(drained[1].device_id, 2);
+        assert_eq!(drained[0].frequency.get::<hertz>(), 50.0);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_drain_skips_unparseable_lines() {
+        let path = temp_queue_path("skip_bad_line");
+        let _ = fs::remove_file(&path);
+        let queue = MeasurementQueue::new(path.clone());
+
+        queue.push(&sample_measurement(1)).unwrap();
+        {
+            let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+            writeln!(file, "{{not valid json").unwrap();
+        }
+        queue.push(&sample_measurement(2)).unwrap();
+
+        let drained = queue.drain().unwrap();
+        assert_eq!(drained.len(), 2);
+        assert_eq!(drained[0].device_id, 1);
+        assert_eq!(drained[1].device_id, 2);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_drain_missing_file_returns_empty() {
+        let path = temp_queue_path("missing");
+        let _ = fs::remove_file(&path);
+        let queue = MeasurementQueue::new(path.clone());
+
+        assert!(queue.drain().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rewrite_overwrites_queue_contents() {
+        let path = temp_queue_path("rewrite");
+        let _ = fs::remove_file(&path);
+        let queue = MeasurementQueue::new(path.clone());
+
+        queue.push(&sample_measurement(1)).unwrap();
+        queue.push(&sample_measurement(2)).unwrap();
+        queue.rewrite(&[sample_measurement(3)]).unwrap();
+
+        let drained = queue.drain().unwrap();
+        assert_eq!(drained.len(), 1);
+        assert_eq!(drained[0].device_id, 3);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
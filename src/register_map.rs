@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use log::debug;
+use serde::Deserialize;
+
+use crate::error::Error;
+use crate::modbus_datatypes::{decode_t5, decode_t6, decode_t17, ModbusConversions};
+use crate::transport::RegisterSource;
+use crate::Counter;
+
+/// Names a conversion from raw registers to a Rust value, as used by the
+/// 7M.24 modbus data types
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConversionKind {
+    T1,
+    T2,
+    T3,
+    T5,
+    T6,
+    T7,
+    T16,
+    T17,
+    Float,
+}
+
+/// One named register (or register pair) to read and decode
+#[derive(Debug, Clone, Deserialize)]
+pub struct RegisterEntry {
+    pub name: String,
+    pub address: u16,
+    pub count: u16,
+    pub conversion: ConversionKind,
+}
+
+/// The four sub-addresses that make up a Finder energy counter
+#[derive(Debug, Clone, Deserialize)]
+pub struct CounterEntry {
+    pub name: String,
+    pub addr_exp: u16,
+    pub addr_mantissa: u16,
+    pub addr_x10: u16,
+    pub addr_float: u16,
+}
+
+/// The set of registers and counters to read from a device, loaded from
+/// `Settings` so a new meter model can be onboarded without recompiling
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RegisterMap {
+    #[serde(default)]
+    pub registers: Vec<RegisterEntry>,
+    #[serde(default)]
+    pub counters: Vec<CounterEntry>,
+}
+
+/// A decoded register value, tagged with the conversion that produced it
+#[derive(Debug, Clone, Copy)]
+pub enum RegisterValue {
+    U16(u16),
+    I16(i16),
+    I32(i32),
+    F32(f32),
+}
+
+impl RegisterValue {
+    pub fn as_i32(self) -> i32 {
+        match self {
+            RegisterValue::U16(v) => v as i32,
+            RegisterValue::I16(v) => v as i32,
+            RegisterValue::I32(v) => v,
+            RegisterValue::F32(v) => v as i32,
+        }
+    }
+
+    pub fn as_f32(self) -> f32 {
+        match self {
+            RegisterValue::U16(v) => v as f32,
+            RegisterValue::I16(v) => v as f32,
+            RegisterValue::I32(v) => v as f32,
+            RegisterValue::F32(v) => v,
+        }
+    }
+}
+
+/// The default register map for a Finder 7M.24 energy meter, matching the
+/// addresses this crate has always read
+pub fn default_register_map() -> RegisterMap {
+    use ConversionKind::*;
+
+    RegisterMap {
+        registers: vec![
+            RegisterEntry { name: "runtime".into(), address: 103, count: 2, conversion: T3 },
+            RegisterEntry { name: "frequency".into(), address: 105, count: 2, conversion: T5 },
+            RegisterEntry { name: "u1".into(), address: 107, count: 2, conversion: T5 },
+            RegisterEntry { name: "i1".into(), address: 126, count: 2, conversion: T5 },
+            RegisterEntry { name: "pt".into(), address: 140, count: 2, conversion: T6 },
+            RegisterEntry { name: "qt".into(), address: 148, count: 2, conversion: T6 },
+            RegisterEntry { name: "st".into(), address: 156, count: 2, conversion: T5 },
+            RegisterEntry { name: "pft".into(), address: 164, count: 2, conversion: T7 },
+            RegisterEntry { name: "temp".into(), address: 181, count: 1, conversion: T17 },
+            RegisterEntry { name: "u1_thd".into(), address: 182, count: 1, conversion: T17 },
+            RegisterEntry { name: "i1_thd".into(), address: 188, count: 1, conversion: T17 },
+        ],
+        counters: vec![
+            // C1 (MID certified) - Import Active Energy
+            CounterEntry { name: "c1".into(), addr_exp: 401, addr_mantissa: 406, addr_x10: 462, addr_float: 2638 },
+            // C4 (MID certified) - Export reactive energy
+            CounterEntry { name: "c4".into(), addr_exp: 404, addr_mantissa: 412, addr_x10: 468, addr_float: 2644 },
+            // X3 (not certified) - Total Absolute Apparent Energy
+            CounterEntry { name: "x3".into(), addr_exp: 448, addr_mantissa: 418, addr_x10: 474, addr_float: 2764 },
+        ],
+    }
+}
+
+/// Read every register and counter named in `map`, dispatching the named
+/// conversion at runtime instead of at compile time
+pub async fn read_register_map<R: RegisterSource + ?Sized>(
+    source: &mut R,
+    map: &RegisterMap,
+) -> Result<(HashMap<String, RegisterValue>, HashMap<String, Counter>), Error> {
+    let mut values = HashMap::with_capacity(map.registers.len());
+    for entry in &map.registers {
+        let raw: Vec<u16> = source
+            .read_input_registers(entry.address, entry.count)
+            .await?;
+
+        let value = match entry.conversion {
+            ConversionKind::T1 => RegisterValue::U16(raw.get_t1()),
+            ConversionKind::T2 => RegisterValue::I16(raw.get_t2()),
+            ConversionKind::T3 => RegisterValue::I32(raw.get_t3()),
+            ConversionKind::T5 => RegisterValue::F32(decode_t5(&raw)),
+            ConversionKind::T6 => RegisterValue::F32(decode_t6(&raw)),
+            ConversionKind::T7 => RegisterValue::I32(raw.get_t7()),
+            ConversionKind::T16 => RegisterValue::F32(raw.get_t16()),
+            ConversionKind::T17 => RegisterValue::F32(decode_t17(&raw)),
+            ConversionKind::Float => RegisterValue::F32(raw.get_float()),
+        };
+        debug!("{} is {:?}: {:?}", entry.name, raw, value);
+
+        values.insert(entry.name.clone(), value);
+    }
+
+    let mut counters = HashMap::with_capacity(map.counters.len());
+    for entry in &map.counters {
+        counters.insert(entry.name.clone(), read_counter(source, entry).await?);
+    }
+
+    Ok((values, counters))
+}
+
+/// Read and assemble a single energy counter from its four sub-addresses
+async fn read_counter<R: RegisterSource + ?Sized>(
+    source: &mut R,
+    entry: &CounterEntry,
+) -> Result<Counter, Error> {
+    let exp = source
+        .read_input_registers(entry.addr_exp, 1)
+        .await?
+        .get_t2() as i32;
+    let mantissa = source
+        .read_input_registers(entry.addr_mantissa, 2)
+        .await?
+        .get_t3();
+    let x10 = source.read_input_registers(entry.addr_x10, 2).await?.get_t3() as f32 / 10.0;
+    let float = source
+        .read_input_registers(entry.addr_float, 2)
+        .await?
+        .get_float();
+
+    let val = (mantissa as f32) * (10.0_f32).powf(exp as f32);
+
+    debug!(
+        "Energy counter {}: exp={} mantissa={} val={} x10={} float={}",
+        entry.name, exp, mantissa, val, x10, float
+    );
+
+    Ok(Counter {
+        exp,
+        mantissa,
+        val,
+        x10,
+        float,
+    })
+}
@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+
+use crate::error::Error;
+
+/// Abstraction over a source of Modbus input registers.
+///
+/// `get_measurements` and `read_register_map` are written against this trait
+/// rather than a concrete `tokio_modbus` context, so RTU, a mock, or a cached
+/// reader can be plugged in without touching the conversion/read logic.
+#[async_trait]
+pub trait RegisterSource {
+    /// Read `count` input registers starting at `addr`
+    async fn read_input_registers(&mut self, addr: u16, count: u16) -> Result<Vec<u16>, Error>;
+}
+
+/// `RegisterSource` backed by a `tokio_modbus` TCP context
+pub struct TcpRegisterSource {
+    ctx: tokio_modbus::client::Context,
+}
+
+impl TcpRegisterSource {
+    /// Connect to a Modbus TCP server and select `device_id` as the slave
+    pub async fn connect(
+        socket_addr: std::net::SocketAddr,
+        device_id: u8,
+    ) -> Result<Self, Error> {
+        use tokio_modbus::prelude::*;
+
+        let mut ctx = tcp::connect(socket_addr).await?;
+        ctx.set_slave(Slave(device_id));
+
+        Ok(Self { ctx })
+    }
+}
+
+#[async_trait]
+impl RegisterSource for TcpRegisterSource {
+    async fn read_input_registers(&mut self, addr: u16, count: u16) -> Result<Vec<u16>, Error> {
+        use tokio_modbus::prelude::*;
+
+        Ok(self.ctx.read_input_registers(addr, count).await?)
+    }
+}